@@ -9,29 +9,41 @@ extern crate js_sys;
 extern crate web_sys;
 
 use fixedbitset::FixedBitSet;
-use js_sys::Math::random;
+use std::collections::VecDeque;
 use std::fmt;
 use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
 use web_sys::console;
 
+/// How many past generations' hashes `tick` keeps around for oscillator
+/// period detection.
+const OSCILLATOR_HISTORY_LEN: usize = 16;
+
 /******************************************************
  *            2) Timer / Debugging Helpers
  *****************************************************/
 /// A simple RAII timer that uses `web_sys::console.time`/`time_end`.
 /// Instantiated at the start of `tick()` to measure how long each `Universe::tick` takes.
+/// The console calls are wasm-bindgen imports, so they're skipped outside
+/// `wasm32` targets (e.g. under `cargo test`) where there's no JS console to call into.
 pub struct Timer<'a> {
+    // Only read by the wasm32 console calls in `Drop::drop` below.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
     name: &'a str,
 }
 
 impl<'a> Timer<'a> {
     pub fn new(name: &'a str) -> Timer<'a> {
+        #[cfg(target_arch = "wasm32")]
         console::time_with_label(name);
+
         Timer { name }
     }
 }
 
 impl<'a> Drop for Timer<'a> {
     fn drop(&mut self) {
+        #[cfg(target_arch = "wasm32")]
         console::time_end_with_label(self.name);
     }
 }
@@ -64,6 +76,28 @@ pub struct Universe {
 
     /// Indices of cells that flipped state this tick.
     changed_cells: Vec<u32>,
+
+    /// Bitmask of live-neighbor counts (0-8) that bring a dead cell to life.
+    /// Bit *n* set means *n* live neighbors triggers birth.
+    birth: u16,
+
+    /// Bitmask of live-neighbor counts (0-8) that keep a live cell alive.
+    /// Bit *n* set means *n* live neighbors lets the cell survive.
+    survival: u16,
+
+    /// Internal xorshift64 state driving `randomize`. Never zero.
+    rng_state: u64,
+
+    /// Number of ticks elapsed since the board was last replaced wholesale
+    /// (creation, `resize`, `clear`, `randomize`, or an RLE load).
+    generation: u32,
+
+    /// FNV-1a hashes of `current` from the last `OSCILLATOR_HISTORY_LEN`
+    /// ticks, oldest first, used to detect oscillator periods.
+    hash_history: VecDeque<u64>,
+
+    /// Oscillation period detected by the most recent tick, if any.
+    detected_period: Option<u32>,
 }
 
 /******************************************************
@@ -89,12 +123,20 @@ impl Universe {
         // next generation buffer
         let next = FixedBitSet::with_capacity(size);
 
+        let (birth, survival) = Universe::parse_rule("B3/S23");
+
         Universe {
             width,
             height,
             current,
             next,
             changed_cells: Vec::new(),
+            birth,
+            survival,
+            rng_state: 0x2545_f491_4f6c_dd1d,
+            generation: 0,
+            hash_history: VecDeque::with_capacity(OSCILLATOR_HISTORY_LEN),
+            detected_period: None,
         }
     }
 
@@ -103,17 +145,9 @@ impl Universe {
         self.width
     }
 
-    /// Adjust universe width, reallocate `current` as all-dead.
+    /// Adjust universe width, preserving the existing pattern. See `resize`.
     pub fn set_width(&mut self, width: u32) {
-        self.width = width;
-        let size = (width * self.height) as usize;
-
-        let mut new_cells = FixedBitSet::with_capacity(size);
-        for i in 0..size {
-            new_cells.set(i, false);
-        }
-
-        self.current = new_cells;
+        self.resize(width, self.height);
     }
 
     /// Universe height
@@ -121,17 +155,59 @@ impl Universe {
         self.height
     }
 
-    /// Adjust universe height, reallocate `current` as all-dead.
+    /// Adjust universe height, preserving the existing pattern. See `resize`.
     pub fn set_height(&mut self, height: u32) {
-        self.height = height;
-        let size    = (height * self.width) as usize;
+        self.resize(self.width, height);
+    }
 
-        let mut new_cells = FixedBitSet::with_capacity(size);
-        for i in 0..size {
-            new_cells.set(i, false);
+    /// Resize the universe to `new_width`×`new_height`, copying every live
+    /// cell that still fits at its same `(row, col)` into fresh `current`
+    /// and `next` buffers. Marks the whole new grid as changed so JS
+    /// redraws at the new dimensions.
+    pub fn resize(&mut self, new_width: u32, new_height: u32) {
+        let new_size = (new_width * new_height) as usize;
+        let mut new_current = FixedBitSet::with_capacity(new_size);
+
+        for row in 0..self.height.min(new_height) {
+            for column in 0..self.width.min(new_width) {
+                let old_index = self.get_index(row, column);
+                if self.current[old_index] {
+                    let new_index = (row * new_width + column) as usize;
+                    new_current.set(new_index, true);
+                }
+            }
+        }
+
+        self.width = new_width;
+        self.height = new_height;
+        self.current = new_current;
+        self.next = FixedBitSet::with_capacity(new_size);
+
+        self.changed_cells.clear();
+        for i in 0..new_size {
+            self.changed_cells.push(i as u32);
         }
 
-        self.current = new_cells;
+        self.reset_generation_tracking();
+    }
+
+    /// Parse a standard B/S rulestring (e.g. "B3/S23", case-insensitive)
+    /// into the `birth`/`survival` neighbor-count masks and apply it.
+    /// Panics if the string isn't of the "B.../S..." form or uses a
+    /// neighbor-count digit greater than 8.
+    pub fn set_rule(&mut self, rule: &str) {
+        let (birth, survival) = Universe::parse_rule(rule);
+        self.birth = birth;
+        self.survival = survival;
+    }
+
+    /// Reconstruct the canonical "B.../S..." rulestring for the current
+    /// birth/survival masks.
+    pub fn rule(&self) -> String {
+        let birth: String = (0..=8).filter(|n| self.birth & (1 << n) != 0).map(|n| n.to_string()).collect();
+        let survival: String = (0..=8).filter(|n| self.survival & (1 << n) != 0).map(|n| n.to_string()).collect();
+
+        format!("B{}/S{}", birth, survival)
     }
 
     /// Return a pointer to the current generation's bits,
@@ -152,6 +228,8 @@ impl Universe {
     ///  - For each cell, calculate next gen in `self.next`.
     ///  - If a cell flips, push its index into `changed_cells`.
     ///  - Swap `current` and `next`.
+    ///  - Bump `generation` and check the new state against the recent
+    ///    hash history to update `detected_period`.
     pub fn tick(&mut self) {
         let _timer = Timer::new("Universe::tick");
         self.changed_cells.clear();
@@ -162,12 +240,10 @@ impl Universe {
                 let old_value    = self.current[index];
                 let live_neighbors = self.live_neighbor_count(row, column);
 
-                let new_value = match (old_value, live_neighbors) {
-                    (true, x) if x < 2 => false,          // Underpopulation
-                    (true, 2) | (true, 3) => true,        // Survive
-                    (true, x) if x > 3 => false,          // Overpopulation
-                    (false, 3) => true,                   // Reproduction
-                    (otherwise, _) => otherwise,
+                let new_value = if old_value {
+                    (self.survival >> live_neighbors) & 1 == 1
+                } else {
+                    (self.birth >> live_neighbors) & 1 == 1
                 };
 
                 self.next.set(index, new_value);
@@ -181,6 +257,27 @@ impl Universe {
 
         // Swap the buffers
         std::mem::swap(&mut self.current, &mut self.next);
+
+        self.generation += 1;
+        self.record_generation_hash();
+    }
+
+    /// Number of ticks elapsed since the board was last replaced wholesale
+    /// (creation, `resize`, `clear`, `randomize`, or an RLE load).
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// True once a tick produces no changed cells, i.e. the board has
+    /// settled into a still life.
+    pub fn is_stable(&self) -> bool {
+        self.generation > 0 && self.changed_cells.is_empty()
+    }
+
+    /// The oscillation period found by matching the current state's hash
+    /// against the last `OSCILLATOR_HISTORY_LEN` generations, if any.
+    pub fn detected_period(&self) -> Option<u32> {
+        self.detected_period
     }
 
     /// Toggle a single cell from alive <-> dead.
@@ -190,13 +287,35 @@ impl Universe {
         self.current.set(index, !current_val);
     }
 
-    /// Randomly set each cell to alive ~50% of the time.
+    /// Seed the deterministic PRNG that drives `randomize`, so soups can be
+    /// reproduced across runs.
+    pub fn seed(&mut self, seed: u64) {
+        self.rng_state = if seed == 0 { 0x2545_f491_4f6c_dd1d } else { seed };
+    }
+
+    /// Randomly set each cell to alive ~50% of the time, drawing from the
+    /// seeded PRNG and recording every flip in `changed_cells`.
     pub fn randomize(&mut self) {
+        self.randomize_with_density(0.5);
+    }
+
+    /// Randomly set each cell to alive with probability `density`, drawing
+    /// from the seeded PRNG and recording every flip in `changed_cells`.
+    pub fn randomize_with_density(&mut self, density: f64) {
+        self.changed_cells.clear();
         let size = (self.width * self.height) as usize;
+
         for i in 0..size {
-            let alive = random() < 0.5;
+            let old_value = self.current[i];
+            let alive = self.next_random() < density;
             self.current.set(i, alive);
+
+            if alive != old_value {
+                self.changed_cells.push(i as u32);
+            }
         }
+
+        self.reset_generation_tracking();
     }
 
     /// Clear entire universe: set all cells dead,
@@ -209,6 +328,8 @@ impl Universe {
             self.current.set(i, false);
             self.changed_cells.push(i as u32);
         }
+
+        self.reset_generation_tracking();
     }
 
     /// Insert a glider pattern around (row, col).
@@ -258,6 +379,65 @@ impl Universe {
         self.set_cells(&positions);
     }
 
+    /// Load an RLE (Run Length Encoded) pattern, clearing the universe first
+    /// and centering the pattern's `x`×`y` bounding box on the board.
+    /// Applies the pattern's `rule =` header (if present) via `set_rule`.
+    pub fn from_rle(&mut self, text: &str) {
+        let pattern = Universe::parse_rle(text);
+        let row_offset = self.height.saturating_sub(pattern.height) / 2;
+        let col_offset = self.width.saturating_sub(pattern.width) / 2;
+
+        self.load_rle_pattern(&pattern, row_offset, col_offset);
+    }
+
+    /// Load an RLE pattern with its top-left corner placed at `(row, column)`
+    /// instead of being centered.
+    pub fn from_rle_at(&mut self, text: &str, row: u32, column: u32) {
+        let pattern = Universe::parse_rle(text);
+        self.load_rle_pattern(&pattern, row, column);
+    }
+
+    /// Serialize the current universe to RLE text, including an
+    /// `x = .., y = .., rule = ..` header and a body with rows separated by
+    /// `$`, trailing dead cells and trailing blank rows omitted, ending in `!`.
+    pub fn to_rle(&self) -> String {
+        let mut body = String::new();
+
+        for row in 0..self.height {
+            let mut run_tag: Option<char> = None;
+            let mut run_len: u32 = 0;
+
+            for column in 0..self.width {
+                let alive = self.current[self.get_index(row, column)];
+                let tag = if alive { 'o' } else { 'b' };
+
+                if run_tag == Some(tag) {
+                    run_len += 1;
+                } else {
+                    if let Some(tag) = run_tag {
+                        Universe::push_rle_run(&mut body, run_len, tag);
+                    }
+                    run_tag = Some(tag);
+                    run_len = 1;
+                }
+            }
+
+            // Suppress the trailing dead run; the end-of-row marker implies it.
+            if run_tag == Some('o') {
+                Universe::push_rle_run(&mut body, run_len, 'o');
+            }
+
+            body.push('$');
+        }
+
+        while body.ends_with('$') {
+            body.pop();
+        }
+        body.push('!');
+
+        format!("x = {}, y = {}, rule = {}\n{}\n", self.width, self.height, self.rule(), body)
+    }
+
     /// Return the pointer to `changed_cells` for JS to read.
     pub fn changed_cells_ptr(&self) -> *const u32 {
         self.changed_cells.as_ptr()
@@ -278,6 +458,62 @@ impl Universe {
         (row * self.width + column) as usize
     }
 
+    /// Advance the xorshift64 PRNG and return the next draw as a float in
+    /// [0, 1).
+    fn next_random(&mut self) -> f64 {
+        let mut state = self.rng_state;
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        self.rng_state = state;
+
+        (state >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Reset generation/oscillator tracking after the board has been
+    /// replaced wholesale, since the old hash history no longer describes
+    /// anything reachable from the new state.
+    fn reset_generation_tracking(&mut self) {
+        self.generation = 0;
+        self.hash_history.clear();
+        self.detected_period = None;
+    }
+
+    /// Hash `current`'s raw bitset slice against the recent hash history to
+    /// update `detected_period`, then push the new hash onto the history,
+    /// evicting the oldest entry past `OSCILLATOR_HISTORY_LEN`.
+    fn record_generation_hash(&mut self) {
+        let hash = Universe::fnv1a_hash(self.current.as_slice());
+
+        self.detected_period = self
+            .hash_history
+            .iter()
+            .rev()
+            .position(|&past_hash| past_hash == hash)
+            .map(|generations_back| (generations_back + 1) as u32);
+
+        self.hash_history.push_back(hash);
+        if self.hash_history.len() > OSCILLATOR_HISTORY_LEN {
+            self.hash_history.pop_front();
+        }
+    }
+
+    /// FNV-1a hash of a bitset's raw block slice.
+    fn fnv1a_hash(blocks: &[usize]) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = FNV_OFFSET_BASIS;
+        for &block in blocks {
+            for byte in (block as u64).to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(FNV_PRIME);
+            }
+        }
+
+        hash
+    }
+
     /// Count how many of the 8 neighbors are alive.
     fn live_neighbor_count(&self, row: u32, column: u32) -> u8 {
         let mut count = 0;
@@ -315,6 +551,44 @@ impl Universe {
         count
     }
 
+    /// Parse a "B.../S..." rulestring into `(birth, survival)` neighbor-count
+    /// masks, where bit *n* of a mask is set if *n* live neighbors triggers
+    /// that transition. Case-insensitive; panics on a malformed string or a
+    /// neighbor-count digit greater than 8.
+    fn parse_rule(rule: &str) -> (u16, u16) {
+        let mut parts = rule.trim().split('/');
+        let b_part = parts.next().expect("rulestring must have a 'B...' part");
+        let s_part = parts.next().expect("rulestring must have a 'S...' part");
+        assert!(parts.next().is_none(), "rulestring must contain exactly one '/'");
+
+        let birth = Universe::parse_rule_part(b_part, 'b');
+        let survival = Universe::parse_rule_part(s_part, 's');
+
+        (birth, survival)
+    }
+
+    /// Parse one "B123" or "S123" side of a rulestring into a neighbor-count
+    /// bitmask, checking that it starts with `expected_prefix`.
+    fn parse_rule_part(part: &str, expected_prefix: char) -> u16 {
+        let mut chars = part.chars();
+        let prefix = chars.next().expect("rulestring part must not be empty");
+        assert!(
+            prefix.to_ascii_lowercase() == expected_prefix,
+            "expected '{}' prefix but found '{}'",
+            expected_prefix,
+            prefix
+        );
+
+        let mut mask: u16 = 0;
+        for digit_char in chars {
+            let digit = digit_char.to_digit(10).expect("neighbor count must be a digit");
+            assert!(digit <= 8, "neighbor count {} is out of range (0-8)", digit);
+            mask |= 1 << digit;
+        }
+
+        mask
+    }
+
     /// Mark these `(row, col)` coordinates as alive in `current`.
     pub fn set_cells(&mut self, cells: &[(u32, u32)]) {
         for &(row, col) in cells {
@@ -327,6 +601,109 @@ impl Universe {
     pub fn get_cells(&self) -> &FixedBitSet {
         &self.current
     }
+
+    /// Clear the universe (which already marks every cell changed), apply
+    /// `pattern`'s rule (if any), and place its live cells at
+    /// `(row_offset, col_offset)`.
+    fn load_rle_pattern(&mut self, pattern: &RlePattern, row_offset: u32, col_offset: u32) {
+        self.clear();
+
+        if let Some(rule) = &pattern.rule {
+            self.set_rule(rule);
+        }
+
+        let positions: Vec<(u32, u32)> = pattern
+            .live_cells
+            .iter()
+            .map(|&(r, c)| ((r + row_offset) % self.height, (c + col_offset) % self.width))
+            .collect();
+
+        self.set_cells(&positions);
+    }
+
+    /// Parse RLE text into a bounding box, optional rule, and the list of
+    /// live `(row, col)` cells relative to the pattern's top-left corner.
+    /// Lines starting with `#` are comments; the `x = m, y = n[, rule = ..]`
+    /// header gives the bounding box and optional rulestring.
+    fn parse_rle(text: &str) -> RlePattern {
+        let mut width = 0;
+        let mut height = 0;
+        let mut rule = None;
+        let mut body = String::new();
+
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('x') {
+                for field in line.split(',') {
+                    let mut kv = field.splitn(2, '=');
+                    let key = kv.next().unwrap_or("").trim();
+                    let value = kv.next().unwrap_or("").trim();
+                    match key {
+                        "x" => width = value.parse().unwrap_or(0),
+                        "y" => height = value.parse().unwrap_or(0),
+                        "rule" => rule = Some(value.to_string()),
+                        _ => {}
+                    }
+                }
+                continue;
+            }
+
+            body.push_str(line);
+        }
+
+        let mut live_cells = Vec::new();
+        let mut row = 0;
+        let mut column = 0;
+        let mut count: u32 = 0;
+
+        for tag in body.chars() {
+            match tag {
+                '0'..='9' => count = count * 10 + tag.to_digit(10).unwrap(),
+                'b' | 'B' => {
+                    column += count.max(1);
+                    count = 0;
+                }
+                'o' | 'O' => {
+                    for _ in 0..count.max(1) {
+                        live_cells.push((row, column));
+                        column += 1;
+                    }
+                    count = 0;
+                }
+                '$' => {
+                    row += count.max(1);
+                    column = 0;
+                    count = 0;
+                }
+                '!' => break,
+                _ => {}
+            }
+        }
+
+        RlePattern { width, height, rule, live_cells }
+    }
+
+    /// Append one RLE run (an optional repeat count followed by its tag)
+    /// to `line`, omitting the count when the run length is 1.
+    fn push_rle_run(line: &mut String, run_len: u32, tag: char) {
+        if run_len > 1 {
+            line.push_str(&run_len.to_string());
+        }
+        line.push(tag);
+    }
+}
+
+/// The bounding box, optional rulestring, and live cells decoded from an
+/// RLE pattern, as produced by `Universe::parse_rle`.
+struct RlePattern {
+    width: u32,
+    height: u32,
+    rule: Option<String>,
+    live_cells: Vec<(u32, u32)>,
 }
 
 /******************************************************
@@ -348,3 +725,147 @@ impl fmt::Display for Universe {
         Ok(())
     }
 }
+
+/******************************************************
+ *                 7) Tests
+ *****************************************************/
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_defaults_to_conways_rule() {
+        let universe = Universe::new();
+        assert_eq!(universe.rule(), "B3/S23");
+    }
+
+    #[test]
+    fn set_rule_is_case_insensitive() {
+        let mut universe = Universe::new();
+        universe.set_rule("b36/s23");
+        assert_eq!(universe.rule(), "B36/S23");
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_rule_rejects_neighbor_count_above_eight() {
+        let mut universe = Universe::new();
+        universe.set_rule("B9/S23");
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_rule_rejects_a_string_missing_the_slash() {
+        let mut universe = Universe::new();
+        universe.set_rule("B3S23");
+    }
+
+    #[test]
+    #[should_panic]
+    fn set_rule_rejects_an_empty_part() {
+        let mut universe = Universe::new();
+        universe.set_rule("B3/");
+    }
+
+    #[test]
+    fn resize_grow_then_tick_does_not_panic() {
+        let mut universe = Universe::new();
+        universe.resize(128, 128);
+        universe.tick();
+    }
+
+    #[test]
+    fn resize_shrink_preserves_in_bounds_cells_and_drops_out_of_bounds() {
+        let mut universe = Universe::new();
+        universe.clear();
+        universe.set_cells(&[(2, 2), (2, 60)]);
+
+        universe.resize(10, 10);
+
+        let in_bounds = universe.get_index(2, 2);
+        assert!(universe.get_cells()[in_bounds]);
+        assert_eq!(universe.get_cells().count_ones(..), 1);
+    }
+
+    #[test]
+    fn glider_round_trips_through_rle() {
+        let mut universe = Universe::new();
+        universe.clear();
+        universe.insert_glider_at(5, 5);
+
+        let rle = universe.to_rle();
+
+        let mut reloaded = Universe::new();
+        reloaded.from_rle(&rle);
+
+        assert_eq!(reloaded.to_rle(), rle);
+    }
+
+    #[test]
+    fn from_rle_applies_rule_header() {
+        let mut universe = Universe::new();
+        universe.from_rle("x = 3, y = 1, rule = B36/S23\n3o!\n");
+
+        assert_eq!(universe.rule(), "B36/S23");
+    }
+
+    #[test]
+    fn to_rle_suppresses_trailing_blank_rows() {
+        let mut universe = Universe::new();
+        universe.clear();
+        universe.set_cells(&[(0, 0)]);
+
+        let rle = universe.to_rle();
+        let body = rle.lines().last().unwrap();
+
+        assert_eq!(body, "o!");
+    }
+
+    #[test]
+    fn same_seed_reproduces_the_same_soup() {
+        let mut a = Universe::new();
+        a.seed(42);
+        a.randomize();
+
+        let mut b = Universe::new();
+        b.seed(42);
+        b.randomize();
+
+        assert_eq!(a.get_cells().as_slice(), b.get_cells().as_slice());
+    }
+
+    #[test]
+    fn seed_zero_does_not_degenerate_the_prng() {
+        let mut universe = Universe::new();
+        universe.seed(0);
+        universe.randomize();
+
+        assert!(universe.get_cells().count_ones(..) > 0);
+    }
+
+    #[test]
+    fn blinker_is_detected_with_period_two() {
+        let mut universe = Universe::new();
+        universe.clear();
+        universe.set_cells(&[(5, 4), (5, 5), (5, 6)]);
+
+        universe.tick();
+        universe.tick();
+        universe.tick();
+
+        assert_eq!(universe.detected_period(), Some(2));
+    }
+
+    #[test]
+    fn block_is_stable_with_period_one() {
+        let mut universe = Universe::new();
+        universe.clear();
+        universe.set_cells(&[(5, 5), (5, 6), (6, 5), (6, 6)]);
+
+        universe.tick();
+        universe.tick();
+
+        assert!(universe.is_stable());
+        assert_eq!(universe.detected_period(), Some(1));
+    }
+}